@@ -0,0 +1,59 @@
+//! A structured error type for malformed ARM expressions, replacing the
+//! panics the parser used to raise on bad input.
+
+use std::fmt;
+
+use crate::functions::FunctionError;
+
+/// A parsing/validation failure in an ARM expression string, carrying the
+/// byte offset within that string where the problem was found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisarmError {
+    UnterminatedString { offset: usize },
+    UnbalancedBrackets { offset: usize },
+    UnexpectedToken { offset: usize, found: String },
+    UnknownFunction { offset: usize, name: String },
+    EmptyArgument { offset: usize },
+    InvalidArgument { offset: usize, source: FunctionError },
+}
+
+impl DisarmError {
+    /// The byte offset within the expression string this error refers to.
+    pub fn offset(&self) -> usize {
+        match self {
+            DisarmError::UnterminatedString { offset }
+            | DisarmError::UnbalancedBrackets { offset }
+            | DisarmError::UnexpectedToken { offset, .. }
+            | DisarmError::UnknownFunction { offset, .. }
+            | DisarmError::EmptyArgument { offset }
+            | DisarmError::InvalidArgument { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for DisarmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DisarmError::UnterminatedString { offset } => {
+                write!(f, "unterminated string literal at byte {offset}")
+            }
+            DisarmError::UnbalancedBrackets { offset } => {
+                write!(f, "unbalanced brackets or parentheses at byte {offset}")
+            }
+            DisarmError::UnexpectedToken { offset, found } => {
+                write!(f, "unexpected {found} at byte {offset}")
+            }
+            DisarmError::UnknownFunction { offset, name } => {
+                write!(f, "unknown function '{name}' at byte {offset}")
+            }
+            DisarmError::EmptyArgument { offset } => {
+                write!(f, "empty argument at byte {offset}")
+            }
+            DisarmError::InvalidArgument { offset, source } => {
+                write!(f, "{source} at byte {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DisarmError {}
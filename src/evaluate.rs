@@ -0,0 +1,455 @@
+//! Resolves `ArmExpression`s to concrete values given a template's
+//! parameters and variables, without needing to actually deploy it.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use crate::{ArmExpression, ArmTemplate, FunctionExpression, FunctionName, LiteralValue};
+
+/// The result of evaluating an [`ArmExpression`].
+///
+/// There's no array literal anywhere in the data model (the parser has
+/// no grammar for `[1, 2, 3]`), so `concat` only ever concatenates
+/// strings here; this enum has nothing to carry an array in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvaluatedValue {
+    Literal(LiteralValue),
+    /// The expression depends on something only known at deployment time
+    /// (a resource function, or an input that couldn't be reduced
+    /// further), carrying the unevaluated node it got stuck on.
+    Unresolved(ArmExpression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    UnknownParameter(String),
+    UnknownVariable(String),
+    CyclicParameter(String),
+    CyclicVariable(String),
+    MissingParameterValue(String),
+    FormatPlaceholderOutOfRange { index: usize, available: usize },
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::UnknownParameter(name) => write!(f, "unknown parameter '{name}'"),
+            EvalError::UnknownVariable(name) => write!(f, "unknown variable '{name}'"),
+            EvalError::CyclicParameter(name) => {
+                write!(f, "cyclic reference while evaluating parameter '{name}'")
+            }
+            EvalError::CyclicVariable(name) => {
+                write!(f, "cyclic reference while evaluating variable '{name}'")
+            }
+            EvalError::MissingParameterValue(name) => {
+                write!(f, "'{name}' has no supplied value and no default")
+            }
+            EvalError::FormatPlaceholderOutOfRange { index, available } => write!(
+                f,
+                "format placeholder {{{index}}} is out of range, only {available} argument(s) supplied"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Tracks parameter/variable names currently being resolved, so a
+/// `defaultValue`/variable definition that (directly or transitively)
+/// refers back to itself is reported as [`EvalError::CyclicParameter`] /
+/// [`EvalError::CyclicVariable`] instead of recursing forever.
+#[derive(Default)]
+struct Visiting {
+    parameters: HashSet<String>,
+    variables: HashSet<String>,
+}
+
+/// Evaluates expressions against a single template's parameters and
+/// variables, resolving `parameters(...)`/`variables(...)` lookups and
+/// the pure built-in functions.
+pub struct EvalContext<'a> {
+    template: &'a ArmTemplate,
+    supplied_parameters: BTreeMap<String, LiteralValue>,
+}
+
+impl<'a> EvalContext<'a> {
+    pub fn new(
+        template: &'a ArmTemplate,
+        supplied_parameters: BTreeMap<String, LiteralValue>,
+    ) -> Self {
+        EvalContext {
+            template,
+            supplied_parameters,
+        }
+    }
+
+    pub fn evaluate(&self, expr: &ArmExpression) -> Result<EvaluatedValue, EvalError> {
+        self.evaluate_inner(expr, &mut Visiting::default())
+    }
+
+    fn evaluate_inner(
+        &self,
+        expr: &ArmExpression,
+        visiting: &mut Visiting,
+    ) -> Result<EvaluatedValue, EvalError> {
+        match expr {
+            ArmExpression::Literal(value) => Ok(EvaluatedValue::Literal(value.clone())),
+            ArmExpression::Parameter(name) => self.evaluate_parameter(name, visiting),
+            ArmExpression::Variable(name) => self.evaluate_variable(name, visiting),
+            ArmExpression::Function(func) => self.evaluate_function(func, visiting),
+            ArmExpression::None
+            | ArmExpression::Reference(_)
+            | ArmExpression::Member(_)
+            | ArmExpression::Index(_) => Ok(EvaluatedValue::Unresolved(expr.clone())),
+        }
+    }
+
+    fn evaluate_parameter(
+        &self,
+        name: &str,
+        visiting: &mut Visiting,
+    ) -> Result<EvaluatedValue, EvalError> {
+        if let Some(value) = self.supplied_parameters.get(name) {
+            return Ok(EvaluatedValue::Literal(value.clone()));
+        }
+
+        if !visiting.parameters.insert(name.to_string()) {
+            return Err(EvalError::CyclicParameter(name.to_string()));
+        }
+
+        let parameter = self
+            .template
+            .parameters
+            .as_ref()
+            .and_then(|parameters| parameters.get(name))
+            .ok_or_else(|| EvalError::UnknownParameter(name.to_string()));
+
+        let result = parameter.and_then(|parameter| match &parameter.default_value {
+            Some(default) => self.evaluate_inner(default, visiting),
+            None => Err(EvalError::MissingParameterValue(name.to_string())),
+        });
+        visiting.parameters.remove(name);
+        result
+    }
+
+    fn evaluate_variable(
+        &self,
+        name: &str,
+        visiting: &mut Visiting,
+    ) -> Result<EvaluatedValue, EvalError> {
+        if !visiting.variables.insert(name.to_string()) {
+            return Err(EvalError::CyclicVariable(name.to_string()));
+        }
+
+        let definition = self
+            .template
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.get(name))
+            .ok_or_else(|| EvalError::UnknownVariable(name.to_string()));
+
+        let result = definition.and_then(|definition| self.evaluate_inner(definition, visiting));
+        visiting.variables.remove(name);
+        result
+    }
+
+    fn evaluate_function(
+        &self,
+        func: &FunctionExpression,
+        visiting: &mut Visiting,
+    ) -> Result<EvaluatedValue, EvalError> {
+        match &func.name {
+            FunctionName::Concat => self.evaluate_concat(func, visiting),
+            FunctionName::Format => self.evaluate_format(func, visiting),
+            FunctionName::If => self.evaluate_if(func, visiting),
+            FunctionName::ResourceId
+            | FunctionName::ResourceGroup
+            | FunctionName::CopyIndex
+            | FunctionName::Unknown(_) => Ok(unresolved_function(func)),
+        }
+    }
+
+    fn evaluate_concat(
+        &self,
+        func: &FunctionExpression,
+        visiting: &mut Visiting,
+    ) -> Result<EvaluatedValue, EvalError> {
+        let mut result = String::new();
+        for argument in &func.arguments {
+            match self.evaluate_inner(argument, visiting)? {
+                EvaluatedValue::Literal(literal) => result.push_str(&literal_to_string(&literal)),
+                EvaluatedValue::Unresolved(_) => return Ok(unresolved_function(func)),
+            }
+        }
+        Ok(EvaluatedValue::Literal(LiteralValue::String(result)))
+    }
+
+    fn evaluate_format(
+        &self,
+        func: &FunctionExpression,
+        visiting: &mut Visiting,
+    ) -> Result<EvaluatedValue, EvalError> {
+        let Some((template_arg, rest)) = func.arguments.split_first() else {
+            return Ok(unresolved_function(func));
+        };
+
+        let template_str = match self.evaluate_inner(template_arg, visiting)? {
+            EvaluatedValue::Literal(LiteralValue::String(s)) => s,
+            _ => return Ok(unresolved_function(func)),
+        };
+
+        let mut substitutions = Vec::with_capacity(rest.len());
+        for argument in rest {
+            match self.evaluate_inner(argument, visiting)? {
+                EvaluatedValue::Literal(literal) => substitutions.push(literal_to_string(&literal)),
+                EvaluatedValue::Unresolved(_) => return Ok(unresolved_function(func)),
+            }
+        }
+
+        substitute_placeholders(&template_str, &substitutions)
+            .map(|formatted| EvaluatedValue::Literal(LiteralValue::String(formatted)))
+            .map_err(|index| EvalError::FormatPlaceholderOutOfRange {
+                index,
+                available: substitutions.len(),
+            })
+    }
+
+    fn evaluate_if(
+        &self,
+        func: &FunctionExpression,
+        visiting: &mut Visiting,
+    ) -> Result<EvaluatedValue, EvalError> {
+        let [condition, when_true, when_false] = func.arguments.as_slice() else {
+            return Ok(unresolved_function(func));
+        };
+
+        match self.evaluate_inner(condition, visiting)? {
+            EvaluatedValue::Literal(LiteralValue::Boolean(true)) => {
+                self.evaluate_inner(when_true, visiting)
+            }
+            EvaluatedValue::Literal(LiteralValue::Boolean(false)) => {
+                self.evaluate_inner(when_false, visiting)
+            }
+            _ => Ok(unresolved_function(func)),
+        }
+    }
+}
+
+fn unresolved_function(func: &FunctionExpression) -> EvaluatedValue {
+    EvaluatedValue::Unresolved(ArmExpression::Function(func.clone()))
+}
+
+fn literal_to_string(value: &LiteralValue) -> String {
+    match value {
+        LiteralValue::String(s) => s.clone(),
+        LiteralValue::Number(n) if n.fract() == 0.0 && n.is_finite() => format!("{}", *n as i64),
+        LiteralValue::Number(n) => n.to_string(),
+        LiteralValue::Boolean(b) => b.to_string(),
+    }
+}
+
+/// Replaces `{0}`, `{1}`, ... placeholders in `template` with the
+/// corresponding entry from `args`. Returns `Err(index)` if a placeholder
+/// references an index out of range.
+fn substitute_placeholders(template: &str, args: &[String]) -> Result<String, usize> {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '{' {
+            out.push(c);
+            continue;
+        }
+
+        let mut digits = String::new();
+        while let Some(&d) = chars.peek() {
+            if !d.is_ascii_digit() {
+                break;
+            }
+            digits.push(d);
+            chars.next();
+        }
+
+        // A run of digits too long to fit `usize` (or not followed by a
+        // closing brace) can't be a placeholder; treat it as literal text
+        // rather than failing to parse it.
+        let index = if chars.peek() == Some(&'}') {
+            digits.parse::<usize>().ok()
+        } else {
+            None
+        };
+
+        let Some(index) = index else {
+            out.push('{');
+            out.push_str(&digits);
+            continue;
+        };
+        chars.next(); // consume '}'
+
+        match args.get(index) {
+            Some(arg) => out.push_str(arg),
+            None => return Err(index),
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArmParameter;
+
+    fn template(
+        parameters: Vec<(&str, Option<ArmExpression>)>,
+        variables: Vec<(&str, ArmExpression)>,
+    ) -> ArmTemplate {
+        ArmTemplate {
+            parameters: Some(
+                parameters
+                    .into_iter()
+                    .map(|(name, default_value)| {
+                        (
+                            name.to_string(),
+                            ArmParameter {
+                                r#type: "string".to_string(),
+                                default_value,
+                            },
+                        )
+                    })
+                    .collect(),
+            ),
+            variables: Some(
+                variables
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+            ),
+            resources: vec![],
+            outputs: None,
+        }
+    }
+
+    fn literal(s: &str) -> ArmExpression {
+        ArmExpression::Literal(LiteralValue::String(s.to_string()))
+    }
+
+    #[test]
+    fn self_referencing_parameter_default_is_a_cyclic_error_not_a_stack_overflow() {
+        let template = template(
+            vec![("a", Some(ArmExpression::Parameter("a".to_string())))],
+            vec![],
+        );
+        let ctx = EvalContext::new(&template, BTreeMap::new());
+
+        pretty_assertions::assert_eq!(
+            ctx.evaluate(&ArmExpression::Parameter("a".to_string())),
+            Err(EvalError::CyclicParameter("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn self_referencing_variable_is_a_cyclic_error() {
+        let template = template(vec![], vec![("a", ArmExpression::Variable("a".to_string()))]);
+        let ctx = EvalContext::new(&template, BTreeMap::new());
+
+        pretty_assertions::assert_eq!(
+            ctx.evaluate(&ArmExpression::Variable("a".to_string())),
+            Err(EvalError::CyclicVariable("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn concat_joins_resolved_string_literals() {
+        let template = template(vec![], vec![]);
+        let ctx = EvalContext::new(&template, BTreeMap::new());
+        let expr = ArmExpression::Function(FunctionExpression {
+            name: FunctionName::Concat,
+            arguments: vec![literal("a"), literal("b")],
+        });
+
+        pretty_assertions::assert_eq!(
+            ctx.evaluate(&expr),
+            Ok(EvaluatedValue::Literal(LiteralValue::String(
+                "ab".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn format_substitutes_placeholders_with_stringified_arguments() {
+        let template = template(vec![], vec![]);
+        let ctx = EvalContext::new(&template, BTreeMap::new());
+        let expr = ArmExpression::Function(FunctionExpression {
+            name: FunctionName::Format,
+            arguments: vec![
+                literal("{0}-{1}"),
+                literal("a"),
+                ArmExpression::Literal(LiteralValue::Number(2.0)),
+            ],
+        });
+
+        pretty_assertions::assert_eq!(
+            ctx.evaluate(&expr),
+            Ok(EvaluatedValue::Literal(LiteralValue::String(
+                "a-2".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn format_placeholder_out_of_range_reports_index_and_available_count() {
+        let template = template(vec![], vec![]);
+        let ctx = EvalContext::new(&template, BTreeMap::new());
+        let expr = ArmExpression::Function(FunctionExpression {
+            name: FunctionName::Format,
+            arguments: vec![literal("{1}"), literal("only one")],
+        });
+
+        pretty_assertions::assert_eq!(
+            ctx.evaluate(&expr),
+            Err(EvalError::FormatPlaceholderOutOfRange {
+                index: 1,
+                available: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn format_placeholder_index_overflowing_usize_is_treated_as_literal_text() {
+        let template = template(vec![], vec![]);
+        let ctx = EvalContext::new(&template, BTreeMap::new());
+        let expr = ArmExpression::Function(FunctionExpression {
+            name: FunctionName::Format,
+            arguments: vec![literal("{99999999999999999999999}")],
+        });
+
+        pretty_assertions::assert_eq!(
+            ctx.evaluate(&expr),
+            Ok(EvaluatedValue::Literal(LiteralValue::String(
+                "{99999999999999999999999}".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn if_selects_the_matching_branch_without_evaluating_the_other() {
+        let template = template(vec![], vec![]);
+        let ctx = EvalContext::new(&template, BTreeMap::new());
+        let expr = ArmExpression::Function(FunctionExpression {
+            name: FunctionName::If,
+            arguments: vec![
+                ArmExpression::Literal(LiteralValue::Boolean(true)),
+                literal("yes"),
+                ArmExpression::Parameter("missing".to_string()),
+            ],
+        });
+
+        pretty_assertions::assert_eq!(
+            ctx.evaluate(&expr),
+            Ok(EvaluatedValue::Literal(LiteralValue::String(
+                "yes".to_string()
+            )))
+        );
+    }
+}
@@ -0,0 +1,328 @@
+//! A data-driven standard library of ARM template functions.
+//!
+//! Rather than hard-coding arities and argument types alongside the
+//! parser, the set of built-in functions is loaded from the bundled
+//! `functions.toml` into a [`FunctionRegistry`]. Callers can extend the
+//! global registry at runtime via [`register_function`] to cover
+//! functions this bundled set doesn't yet know about.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::{ArmExpression, FunctionExpression, LiteralValue};
+
+/// The kind of value an argument or return position expects.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgKind {
+    String,
+    Int,
+    Bool,
+    /// Anything: a nested function call, parameter/variable reference, or
+    /// a literal whose kind can't be pinned down ahead of evaluation.
+    Expression,
+}
+
+impl fmt::Display for ArgKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ArgKind::String => "string",
+            ArgKind::Int => "int",
+            ArgKind::Bool => "bool",
+            ArgKind::Expression => "expression",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The arity and argument/return kinds of a single ARM function.
+#[derive(Debug, Clone)]
+pub struct FunctionSignature {
+    pub min_args: usize,
+    /// `None` means the function is variadic (no upper bound).
+    pub max_args: Option<usize>,
+    pub argument_kinds: Vec<ArgKind>,
+    pub return_kind: ArgKind,
+}
+
+impl FunctionSignature {
+    /// The expected kind at `index`, falling back to the last declared
+    /// kind for variadic trailing arguments.
+    fn argument_kind_at(&self, index: usize) -> ArgKind {
+        self.argument_kinds
+            .get(index)
+            .or_else(|| self.argument_kinds.last())
+            .copied()
+            .unwrap_or(ArgKind::Expression)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionDef {
+    name: String,
+    min_args: usize,
+    #[serde(default)]
+    max_args: Option<usize>,
+    #[serde(default)]
+    variadic: bool,
+    #[serde(default)]
+    argument_kinds: Vec<ArgKind>,
+    return_kind: ArgKind,
+}
+
+impl From<FunctionDef> for FunctionSignature {
+    fn from(def: FunctionDef) -> Self {
+        FunctionSignature {
+            min_args: def.min_args,
+            max_args: if def.variadic { None } else { def.max_args },
+            argument_kinds: def.argument_kinds,
+            return_kind: def.return_kind,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionDefs {
+    function: Vec<FunctionDef>,
+}
+
+const BUNDLED_FUNCTIONS: &str = include_str!("functions.toml");
+
+/// A lookup table of known function signatures, used to validate parsed
+/// [`FunctionExpression`]s.
+#[derive(Debug, Default)]
+pub struct FunctionRegistry {
+    signatures: HashMap<String, FunctionSignature>,
+}
+
+impl FunctionRegistry {
+    fn with_builtins() -> Self {
+        let defs: FunctionDefs =
+            toml::from_str(BUNDLED_FUNCTIONS).expect("bundled functions.toml is valid");
+
+        let signatures = defs
+            .function
+            .into_iter()
+            .map(|def| (def.name.clone(), FunctionSignature::from(def)))
+            .collect();
+
+        FunctionRegistry { signatures }
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, signature: FunctionSignature) {
+        self.signatures.insert(name.into(), signature);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FunctionSignature> {
+        self.signatures.get(name)
+    }
+}
+
+static REGISTRY: Lazy<RwLock<FunctionRegistry>> =
+    Lazy::new(|| RwLock::new(FunctionRegistry::with_builtins()));
+
+/// Registers (or overrides) the signature of a function by name in the
+/// global registry, so templates using it pass validation.
+pub fn register_function(name: impl Into<String>, signature: FunctionSignature) {
+    REGISTRY
+        .write()
+        .expect("function registry lock poisoned")
+        .register(name, signature);
+}
+
+/// An error produced while validating a parsed function call against its
+/// registered signature.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionError {
+    UnknownFunction(String),
+    TooFewArguments {
+        name: String,
+        min: usize,
+        got: usize,
+    },
+    TooManyArguments {
+        name: String,
+        max: usize,
+        got: usize,
+    },
+    WrongArgumentKind {
+        name: String,
+        index: usize,
+        expected: ArgKind,
+        got: ArgKind,
+    },
+}
+
+impl fmt::Display for FunctionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FunctionError::UnknownFunction(name) => write!(f, "unknown function '{name}'"),
+            FunctionError::TooFewArguments { name, min, got } => write!(
+                f,
+                "'{name}' expects at least {min} argument(s), got {got}"
+            ),
+            FunctionError::TooManyArguments { name, max, got } => write!(
+                f,
+                "'{name}' expects at most {max} argument(s), got {got}"
+            ),
+            FunctionError::WrongArgumentKind {
+                name,
+                index,
+                expected,
+                got,
+            } => write!(
+                f,
+                "'{name}' argument {index} expected {expected}, got {got}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FunctionError {}
+
+fn literal_kind(expr: &ArmExpression) -> Option<ArgKind> {
+    match expr {
+        ArmExpression::Literal(LiteralValue::String(_)) => Some(ArgKind::String),
+        ArmExpression::Literal(LiteralValue::Number(_)) => Some(ArgKind::Int),
+        ArmExpression::Literal(LiteralValue::Boolean(_)) => Some(ArgKind::Bool),
+        _ => None,
+    }
+}
+
+/// Validates a parsed function call against the global registry's
+/// signature for its name.
+pub fn validate(expr: &FunctionExpression) -> Result<(), FunctionError> {
+    let registry = REGISTRY.read().expect("function registry lock poisoned");
+    let name = expr.name.as_str();
+    let signature = registry
+        .get(name)
+        .ok_or_else(|| FunctionError::UnknownFunction(name.to_string()))?;
+
+    let got = expr.arguments.len();
+    if got < signature.min_args {
+        return Err(FunctionError::TooFewArguments {
+            name: name.to_string(),
+            min: signature.min_args,
+            got,
+        });
+    }
+    if let Some(max) = signature.max_args {
+        if got > max {
+            return Err(FunctionError::TooManyArguments {
+                name: name.to_string(),
+                max,
+                got,
+            });
+        }
+    }
+
+    for (index, arg) in expr.arguments.iter().enumerate() {
+        let expected = signature.argument_kind_at(index);
+        if expected == ArgKind::Expression {
+            continue;
+        }
+        if let Some(got) = literal_kind(arg) {
+            if got != expected {
+                return Err(FunctionError::WrongArgumentKind {
+                    name: name.to_string(),
+                    index,
+                    expected,
+                    got,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FunctionName;
+
+    fn call(name: FunctionName, arguments: Vec<ArmExpression>) -> FunctionExpression {
+        FunctionExpression { name, arguments }
+    }
+
+    #[test]
+    fn unknown_function_is_rejected() {
+        let expr = call(FunctionName::Unknown("notAFunction".to_string()), vec![]);
+        pretty_assertions::assert_eq!(
+            validate(&expr),
+            Err(FunctionError::UnknownFunction("notAFunction".to_string()))
+        );
+    }
+
+    #[test]
+    fn too_few_arguments_is_rejected() {
+        let expr = call(FunctionName::ResourceId, vec![]);
+        pretty_assertions::assert_eq!(
+            validate(&expr),
+            Err(FunctionError::TooFewArguments {
+                name: "resourceId".to_string(),
+                min: 2,
+                got: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn too_many_arguments_is_rejected() {
+        let expr = call(
+            FunctionName::If,
+            vec![
+                ArmExpression::Literal(LiteralValue::Boolean(true)),
+                ArmExpression::Literal(LiteralValue::String("a".to_string())),
+                ArmExpression::Literal(LiteralValue::String("b".to_string())),
+                ArmExpression::Literal(LiteralValue::String("c".to_string())),
+            ],
+        );
+        pretty_assertions::assert_eq!(
+            validate(&expr),
+            Err(FunctionError::TooManyArguments {
+                name: "if".to_string(),
+                max: 3,
+                got: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn wrong_argument_kind_is_rejected() {
+        let expr = call(
+            FunctionName::If,
+            vec![
+                ArmExpression::Literal(LiteralValue::String("not a bool".to_string())),
+                ArmExpression::Literal(LiteralValue::String("a".to_string())),
+                ArmExpression::Literal(LiteralValue::String("b".to_string())),
+            ],
+        );
+        pretty_assertions::assert_eq!(
+            validate(&expr),
+            Err(FunctionError::WrongArgumentKind {
+                name: "if".to_string(),
+                index: 0,
+                expected: ArgKind::Bool,
+                got: ArgKind::String,
+            })
+        );
+    }
+
+    #[test]
+    fn variadic_call_within_arity_and_kinds_is_accepted() {
+        let expr = call(
+            FunctionName::Concat,
+            vec![
+                ArmExpression::Literal(LiteralValue::String("a".to_string())),
+                ArmExpression::Literal(LiteralValue::String("b".to_string())),
+                ArmExpression::Literal(LiteralValue::String("c".to_string())),
+            ],
+        );
+        pretty_assertions::assert_eq!(validate(&expr), Ok(()));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::error::DisarmError;
+
+/// A single lexical token scanned out of an ARM expression string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Comma,
+    Dot,
+    Ident(String),
+    String(String),
+    Int(i64),
+    True,
+    False,
+}
+
+/// Scans `value` into a flat token stream, pairing each token with the
+/// byte offset (within `value`) it started at.
+///
+/// Single-quoted strings use the ARM convention of doubling a quote
+/// (`''`) to escape a literal `'` inside the string.
+pub fn tokenize(value: &str) -> Result<Vec<(Token, usize)>, DisarmError> {
+    let mut chars = value.char_indices().peekable();
+    let mut tokens = Vec::new();
+
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            '[' => {
+                tokens.push((Token::LBracket, start));
+                chars.next();
+            }
+            ']' => {
+                tokens.push((Token::RBracket, start));
+                chars.next();
+            }
+            '(' => {
+                tokens.push((Token::LParen, start));
+                chars.next();
+            }
+            ')' => {
+                tokens.push((Token::RParen, start));
+                chars.next();
+            }
+            ',' => {
+                tokens.push((Token::Comma, start));
+                chars.next();
+            }
+            '.' => {
+                tokens.push((Token::Dot, start));
+                chars.next();
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '\'' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err(DisarmError::UnterminatedString { offset: start }),
+                        Some((_, '\'')) => {
+                            if matches!(chars.peek(), Some((_, '\''))) {
+                                s.push('\'');
+                                chars.next();
+                                continue;
+                            }
+                            break;
+                        }
+                        Some((_, c)) => s.push(c),
+                    }
+                }
+                tokens.push((Token::String(s), start));
+            }
+            c if c.is_ascii_digit() => {
+                let mut end = start + c.len_utf8();
+                chars.next();
+                while let Some(&(pos, c)) = chars.peek() {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    end = pos + c.len_utf8();
+                    chars.next();
+                }
+                let text = &value[start..end];
+                let n = text.parse::<i64>().map_err(|_| DisarmError::UnexpectedToken {
+                    offset: start,
+                    found: format!("invalid integer literal '{text}'"),
+                })?;
+                tokens.push((Token::Int(n), start));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                chars.next();
+                while let Some(&(pos, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = pos + c.len_utf8();
+                    chars.next();
+                }
+                let text = &value[start..end];
+                tokens.push((
+                    match text {
+                        "true" => Token::True,
+                        "false" => Token::False,
+                        _ => Token::Ident(text.to_string()),
+                    },
+                    start,
+                ));
+            }
+            other => {
+                return Err(DisarmError::UnexpectedToken {
+                    offset: start,
+                    found: format!("character '{other}'"),
+                })
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(value: &str) -> Vec<Token> {
+        tokenize(value)
+            .expect("parseable")
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_a_function_call() {
+        pretty_assertions::assert_eq!(
+            tokens("[concat('a', 'b')]"),
+            vec![
+                Token::LBracket,
+                Token::Ident("concat".to_string()),
+                Token::LParen,
+                Token::String("a".to_string()),
+                Token::Comma,
+                Token::String("b".to_string()),
+                Token::RParen,
+                Token::RBracket,
+            ]
+        );
+    }
+
+    #[test]
+    fn doubled_single_quote_decodes_to_a_literal_quote() {
+        pretty_assertions::assert_eq!(
+            tokens("'it''s'"),
+            vec![Token::String("it's".to_string())]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_reports_the_opening_quote_offset() {
+        let err = tokenize("[concat('a)]").unwrap_err();
+        pretty_assertions::assert_eq!(err, DisarmError::UnterminatedString { offset: 8 });
+    }
+
+    #[test]
+    fn recognizes_keywords_and_integers() {
+        pretty_assertions::assert_eq!(
+            tokens("if(true, 1, 2)"),
+            vec![
+                Token::Ident("if".to_string()),
+                Token::LParen,
+                Token::True,
+                Token::Comma,
+                Token::Int(1),
+                Token::Comma,
+                Token::Int(2),
+                Token::RParen,
+            ]
+        );
+    }
+
+    #[test]
+    fn member_and_index_access_tokenize_as_dot_and_brackets() {
+        pretty_assertions::assert_eq!(
+            tokens("variables('a')[0].b"),
+            vec![
+                Token::Ident("variables".to_string()),
+                Token::LParen,
+                Token::String("a".to_string()),
+                Token::RParen,
+                Token::LBracket,
+                Token::Int(0),
+                Token::RBracket,
+                Token::Dot,
+                Token::Ident("b".to_string()),
+            ]
+        );
+    }
+}
@@ -1,81 +1,124 @@
 use std::collections::BTreeMap;
 
 use serde::de::{self, Visitor};
+use serde::ser::Serializer;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt;
 
-#[derive(Debug, Serialize, Clone, PartialEq)]
+mod error;
+mod evaluate;
+mod functions;
+mod lexer;
+mod parser;
+
+use parser::parse_expression;
+
+pub use error::DisarmError;
+pub use evaluate::{EvalContext, EvalError, EvaluatedValue};
+pub use functions::{register_function, ArgKind, FunctionError, FunctionRegistry, FunctionSignature};
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ArmExpression {
     Literal(LiteralValue),
     Function(FunctionExpression),
     Parameter(String),
     Variable(String),
     Reference(ReferenceExpression),
+    Member(MemberExpression),
+    Index(IndexExpression),
     None,
 }
 
-fn parse_expression<E>(value: &str) -> Result<ArmExpression, E>
-where
-    E: de::Error,
-{
-    dbg!(value);
-    match value {
-        _ if value.is_empty() => Ok(ArmExpression::None),
-        _ if value.starts_with("[variables(") => {
-            let inner = &value[12..value.len() - 3];
-            Ok(ArmExpression::Variable(inner.to_string()))
-        }
-        _ if value.starts_with("variables(") => {
-            let inner = &value[11..value.len() - 2];
-            Ok(ArmExpression::Variable(inner.to_string()))
-        }
-        _ if value.starts_with("[parameters(") => {
-            let inner = &value[13..value.len() - 3];
-            Ok(ArmExpression::Parameter(inner.to_string()))
+impl ArmExpression {
+    /// Renders this expression back to its canonical ARM string form,
+    /// e.g. `[concat(parameters('a'), 'b')]`. The outermost expression is
+    /// wrapped in `[` ... `]` unless it's a bare literal.
+    fn to_arm_string(&self) -> String {
+        match self {
+            ArmExpression::None => String::new(),
+            ArmExpression::Literal(value) => render_literal(value),
+            other => format!("[{}]", other.render()),
         }
-        _ if value.starts_with("parameters(") => {
-            let inner = &value[12..value.len() - 2];
-            Ok(ArmExpression::Parameter(inner.to_string()))
-        }
-        _ if value.starts_with("[") => {
-            let first_opening_parenthesis = value.find("(").expect("opening parenthesis");
-            let last_closing_parenthesis = value.rfind(")").expect("closing bracket");
-
-            let function_name_str = &value[1..first_opening_parenthesis]; // Extract arguments from "format(...)"
-            let args_str = &value[first_opening_parenthesis + 1..last_closing_parenthesis];
-            let args: Vec<&str> = args_str.split(",").map(|s| s.trim()).collect(); // Split and trim arguments
-
-            // For simplicity, assuming arguments are either strings or other simple literals
-            let parsed_args: Vec<ArmExpression> = args
-                .iter()
-                .map(|arg| parse_expression::<E>(arg).expect("parseable"))
-                .collect();
-
-            let function_name = match function_name_str {
-                "format" => FunctionName::Format,
-                "concat" => FunctionName::Concat,
-                "copyIndex" => FunctionName::CopyIndex,
-                "resourceId" => FunctionName::ResourceId,
-                "if" => FunctionName::If,
-                "resourceGroup" => FunctionName::ResourceGroup,
-                _ => todo!(),
-            };
-
-            return Ok(ArmExpression::Function(FunctionExpression {
-                name: function_name,
-                arguments: parsed_args,
-            }));
+    }
+
+    /// Renders this expression in its "inside an expression" form, i.e.
+    /// without the surrounding `[` ... `]` and with string literals
+    /// single-quoted.
+    fn render(&self) -> String {
+        match self {
+            ArmExpression::None => String::new(),
+            ArmExpression::Literal(value) => render_literal_quoted(value),
+            ArmExpression::Parameter(name) => format!("parameters({})", render_string(name)),
+            ArmExpression::Variable(name) => format!("variables({})", render_string(name)),
+            ArmExpression::Function(func) => {
+                let args = func
+                    .arguments
+                    .iter()
+                    .map(ArmExpression::render)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{}({})", func.name.as_str(), args)
+            }
+            ArmExpression::Reference(reference) => {
+                let api_version = reference
+                    .api_version
+                    .as_deref()
+                    .map(|v| format!(", {}", render_string(v)))
+                    .unwrap_or_default();
+                format!(
+                    "reference({}{})",
+                    render_string(&reference.resource_name),
+                    api_version
+                )
+            }
+            ArmExpression::Member(member) => format!("{}.{}", member.target.render(), member.member),
+            ArmExpression::Index(index) => {
+                format!("{}[{}]", index.target.render(), index.index.render())
+            }
         }
-        // TODO messy argument check, find a better way to parse functions/args?
-        _ if value.starts_with("'") => Ok(ArmExpression::Literal(LiteralValue::String(
-            value[1..value.len() - 1].to_string(),
-        ))),
-        // TODO We are still in arguments, and could find a function!
-        _ => Ok(ArmExpression::Literal(LiteralValue::String(
-            value.to_string(),
-        ))),
     }
 }
+
+fn render_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn render_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.is_finite() {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+/// Renders a literal the way it appears as a bare, top-level value: a
+/// string literal is unquoted since it IS the value, not ARM syntax.
+fn render_literal(value: &LiteralValue) -> String {
+    match value {
+        LiteralValue::String(s) => s.clone(),
+        LiteralValue::Number(n) => render_number(*n),
+        LiteralValue::Boolean(b) => b.to_string(),
+    }
+}
+
+/// Renders a literal as it appears nested inside an expression, where a
+/// string needs its ARM single-quote syntax.
+fn render_literal_quoted(value: &LiteralValue) -> String {
+    match value {
+        LiteralValue::String(s) => render_string(s),
+        _ => render_literal(value),
+    }
+}
+
+impl Serialize for ArmExpression {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_arm_string())
+    }
+}
+
 impl<'de> Deserialize<'de> for ArmExpression {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -94,7 +137,7 @@ impl<'de> Deserialize<'de> for ArmExpression {
             where
                 E: de::Error,
             {
-                parse_expression(value)
+                parse_expression(value).map_err(de::Error::custom)
             }
         }
 
@@ -123,6 +166,22 @@ pub struct ReferenceExpression {
     pub api_version: Option<String>, // Some references may require an API version
 }
 
+/// Property access on another expression, e.g. the `.outputs` in
+/// `reference(...).outputs`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MemberExpression {
+    pub target: Box<ArmExpression>,
+    pub member: String,
+}
+
+/// Array/index access on another expression, e.g. the `[0]` in
+/// `variables('a')[0]`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IndexExpression {
+    pub target: Box<ArmExpression>,
+    pub index: Box<ArmExpression>,
+}
+
 // Example predefined functions like concat(), resourceId(), etc.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum FunctionName {
@@ -132,6 +191,26 @@ pub enum FunctionName {
     Format,
     If,
     ResourceGroup,
+    /// A function name not among the well-known variants above. The parser
+    /// runs `functions::validate` on every call, which rejects it as
+    /// unrecognized unless the registry has been extended to cover it.
+    Unknown(String),
+}
+
+impl FunctionName {
+    /// The canonical ARM name this variant was parsed from, used both to
+    /// look it up in the function registry and to re-serialize it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            FunctionName::Concat => "concat",
+            FunctionName::ResourceId => "resourceId",
+            FunctionName::CopyIndex => "copyIndex",
+            FunctionName::Format => "format",
+            FunctionName::If => "if",
+            FunctionName::ResourceGroup => "resourceGroup",
+            FunctionName::Unknown(name) => name,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -190,4 +269,28 @@ mod tests {
 
         pretty_assertions::assert_eq!(template, key);
     }
+
+    /// Parses every fixture in `data/`, re-serializes it, and asserts the
+    /// result is identical to the source JSON, i.e. every `ArmExpression`
+    /// round-trips back to its original string form.
+    #[test]
+    fn round_trips_data_files() {
+        for entry in std::fs::read_dir("data").expect("data dir exists") {
+            let path = entry.expect("readable dir entry").path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let raw = std::fs::read_to_string(&path).expect("readable file");
+            let original: serde_json::Value = serde_json::from_str(&raw).expect("valid json");
+            let template: ArmTemplate = serde_json::from_str(&raw).expect("parseable");
+            let round_tripped = serde_json::to_value(&template).expect("serializable");
+
+            pretty_assertions::assert_eq!(
+                original,
+                round_tripped,
+                "{path:?} did not round-trip through ArmTemplate"
+            );
+        }
+    }
 }
@@ -0,0 +1,415 @@
+use crate::error::DisarmError;
+use crate::lexer::{tokenize, Token};
+use crate::{
+    functions, ArmExpression, FunctionExpression, FunctionName, IndexExpression, LiteralValue,
+    MemberExpression, ReferenceExpression,
+};
+
+/// Parses an ARM expression string into an [`ArmExpression`].
+///
+/// A value is only ever treated as the expression grammar below when it
+/// starts with `[` (and it must then end with the matching `]`, or parsing
+/// fails); everything else (the common case for fields
+/// like `defaultValue`, locations, SKU names, ...) is a plain literal
+/// string, taken verbatim.
+///
+/// ```text
+/// top    := '[' expr ']'
+/// expr   := primary ('.' ident | '[' expr ']')*
+/// primary:= literal | ident '(' args ')'
+/// args   := (expr (',' expr)*)?
+/// ```
+pub fn parse_expression(value: &str) -> Result<ArmExpression, DisarmError> {
+    if value.is_empty() {
+        return Ok(ArmExpression::None);
+    }
+
+    if !value.starts_with('[') {
+        return Ok(ArmExpression::Literal(LiteralValue::String(
+            value.to_string(),
+        )));
+    }
+
+    let tokens = tokenize(value)?;
+    let end = value.len();
+    let mut parser = Parser { tokens, pos: 0, end };
+    parser.parse_top_level()
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Byte offset just past the end of the expression string, used to
+    /// report errors that occur at end-of-input.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn current_offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|&(_, offset)| offset)
+            .unwrap_or(self.end)
+    }
+
+    fn advance(&mut self) -> Option<(Token, usize)> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), DisarmError> {
+        let offset = self.current_offset();
+        match self.advance() {
+            Some((token, _)) if token == expected => Ok(()),
+            _ => match expected {
+                Token::LParen | Token::RParen | Token::LBracket | Token::RBracket => {
+                    Err(DisarmError::UnbalancedBrackets { offset })
+                }
+                _ => Err(DisarmError::UnexpectedToken {
+                    offset,
+                    found: "end of expression".to_string(),
+                }),
+            },
+        }
+    }
+
+    fn parse_top_level(&mut self) -> Result<ArmExpression, DisarmError> {
+        // `parse_expression` only tokenizes and calls this when `value`
+        // starts with '[', so the leading bracket is always present here.
+        self.expect(Token::LBracket)?;
+        let expr = self.parse_expr()?;
+        self.expect(Token::RBracket)?;
+
+        if self.pos != self.tokens.len() {
+            return Err(DisarmError::UnexpectedToken {
+                offset: self.current_offset(),
+                found: "trailing tokens".to_string(),
+            });
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_expr(&mut self) -> Result<ArmExpression, DisarmError> {
+        let mut node = self.parse_primary()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.advance();
+                    let offset = self.current_offset();
+                    let member = match self.advance() {
+                        Some((Token::Ident(name), _)) => name,
+                        _ => {
+                            return Err(DisarmError::UnexpectedToken {
+                                offset,
+                                found: "token after '.'".to_string(),
+                            })
+                        }
+                    };
+                    node = ArmExpression::Member(MemberExpression {
+                        target: Box::new(node),
+                        member,
+                    });
+                }
+                Some(Token::LBracket) => {
+                    self.advance();
+                    let index = self.parse_expr()?;
+                    self.expect(Token::RBracket)?;
+                    node = ArmExpression::Index(IndexExpression {
+                        target: Box::new(node),
+                        index: Box::new(index),
+                    });
+                }
+                _ => break,
+            }
+        }
+
+        Ok(node)
+    }
+
+    fn parse_primary(&mut self) -> Result<ArmExpression, DisarmError> {
+        let offset = self.current_offset();
+        match self.advance() {
+            Some((Token::String(s), _)) => Ok(ArmExpression::Literal(LiteralValue::String(s))),
+            Some((Token::Int(n), _)) => Ok(ArmExpression::Literal(LiteralValue::Number(n as f64))),
+            Some((Token::True, _)) => Ok(ArmExpression::Literal(LiteralValue::Boolean(true))),
+            Some((Token::False, _)) => Ok(ArmExpression::Literal(LiteralValue::Boolean(false))),
+            Some((Token::Ident(name), _)) => {
+                self.expect(Token::LParen)?;
+                let args = self.parse_args()?;
+                self.expect(Token::RParen)?;
+                build_call(name, args, offset)
+            }
+            Some((token, _)) => Err(DisarmError::UnexpectedToken {
+                offset,
+                found: format!("token {token:?}"),
+            }),
+            None => Err(DisarmError::UnexpectedToken {
+                offset,
+                found: "end of expression".to_string(),
+            }),
+        }
+    }
+
+    fn parse_args(&mut self) -> Result<Vec<ArmExpression>, DisarmError> {
+        let mut args = Vec::new();
+
+        if matches!(self.peek(), Some(Token::RParen)) {
+            return Ok(args);
+        }
+
+        loop {
+            if matches!(self.peek(), Some(Token::Comma) | Some(Token::RParen)) {
+                return Err(DisarmError::EmptyArgument {
+                    offset: self.current_offset(),
+                });
+            }
+            args.push(self.parse_expr()?);
+            match self.peek() {
+                Some(Token::Comma) => {
+                    self.advance();
+                }
+                _ => break,
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Turns a parsed `name(args...)` call into the right [`ArmExpression`]
+/// variant, special-casing `parameters(...)`, `variables(...)` and
+/// `reference(...)` which the data model represents directly rather than
+/// as a [`FunctionExpression`].
+fn build_call(
+    name: String,
+    args: Vec<ArmExpression>,
+    offset: usize,
+) -> Result<ArmExpression, DisarmError> {
+    match name.as_str() {
+        "parameters" => match args.as_slice() {
+            [ArmExpression::Literal(LiteralValue::String(s))] => {
+                Ok(ArmExpression::Parameter(s.clone()))
+            }
+            _ => Err(DisarmError::UnexpectedToken {
+                offset,
+                found: "parameters() expects a single string literal argument".to_string(),
+            }),
+        },
+        "variables" => match args.as_slice() {
+            [ArmExpression::Literal(LiteralValue::String(s))] => {
+                Ok(ArmExpression::Variable(s.clone()))
+            }
+            _ => Err(DisarmError::UnexpectedToken {
+                offset,
+                found: "variables() expects a single string literal argument".to_string(),
+            }),
+        },
+        "reference" => match args.as_slice() {
+            [ArmExpression::Literal(LiteralValue::String(resource_name))] => {
+                Ok(ArmExpression::Reference(ReferenceExpression {
+                    resource_name: resource_name.clone(),
+                    api_version: None,
+                }))
+            }
+            [ArmExpression::Literal(LiteralValue::String(resource_name)), ArmExpression::Literal(LiteralValue::String(api_version))] => {
+                Ok(ArmExpression::Reference(ReferenceExpression {
+                    resource_name: resource_name.clone(),
+                    api_version: Some(api_version.clone()),
+                }))
+            }
+            _ => Err(DisarmError::UnexpectedToken {
+                offset,
+                found: "reference() expects a resource name and optional api version as string literals".to_string(),
+            }),
+        },
+        _ => {
+            let function_name = match name.as_str() {
+                "format" => FunctionName::Format,
+                "concat" => FunctionName::Concat,
+                "copyIndex" => FunctionName::CopyIndex,
+                "resourceId" => FunctionName::ResourceId,
+                "if" => FunctionName::If,
+                "resourceGroup" => FunctionName::ResourceGroup,
+                _ => FunctionName::Unknown(name),
+            };
+            let expr = FunctionExpression {
+                name: function_name,
+                arguments: args,
+            };
+            functions::validate(&expr).map_err(|source| match source {
+                functions::FunctionError::UnknownFunction(name) => {
+                    DisarmError::UnknownFunction { offset, name }
+                }
+                other => DisarmError::InvalidArgument {
+                    offset,
+                    source: other,
+                },
+            })?;
+            Ok(ArmExpression::Function(expr))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_non_bracketed_values_are_literal_strings() {
+        for value in ["westus", "Standard_LRS", "Microsoft.Storage/storageAccounts", "my-function-app"] {
+            pretty_assertions::assert_eq!(
+                parse_expression(value).expect("parseable"),
+                ArmExpression::Literal(LiteralValue::String(value.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn nested_function_calls_with_commas_in_arguments() {
+        let expr = parse_expression(
+            "[concat(resourceId('Microsoft.Web/sites', 'a'), variables('c'))]",
+        )
+        .expect("parseable");
+
+        pretty_assertions::assert_eq!(
+            expr,
+            ArmExpression::Function(FunctionExpression {
+                name: FunctionName::Concat,
+                arguments: vec![
+                    ArmExpression::Function(FunctionExpression {
+                        name: FunctionName::ResourceId,
+                        arguments: vec![
+                            ArmExpression::Literal(LiteralValue::String(
+                                "Microsoft.Web/sites".to_string()
+                            )),
+                            ArmExpression::Literal(LiteralValue::String("a".to_string())),
+                        ],
+                    }),
+                    ArmExpression::Variable("c".to_string()),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn doubled_quotes_decode_to_a_literal_quote_in_arguments() {
+        let expr = parse_expression("[format('it''s {0}', 'ok')]").expect("parseable");
+        pretty_assertions::assert_eq!(
+            expr,
+            ArmExpression::Function(FunctionExpression {
+                name: FunctionName::Format,
+                arguments: vec![
+                    ArmExpression::Literal(LiteralValue::String("it's {0}".to_string())),
+                    ArmExpression::Literal(LiteralValue::String("ok".to_string())),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn property_access_and_array_indexer_fold_as_postfix() {
+        pretty_assertions::assert_eq!(
+            parse_expression("[reference('foo').outputs.x]").expect("parseable"),
+            ArmExpression::Member(MemberExpression {
+                target: Box::new(ArmExpression::Member(MemberExpression {
+                    target: Box::new(ArmExpression::Reference(ReferenceExpression {
+                        resource_name: "foo".to_string(),
+                        api_version: None,
+                    })),
+                    member: "outputs".to_string(),
+                })),
+                member: "x".to_string(),
+            })
+        );
+
+        pretty_assertions::assert_eq!(
+            parse_expression("[variables('a')[0]]").expect("parseable"),
+            ArmExpression::Index(IndexExpression {
+                target: Box::new(ArmExpression::Variable("a".to_string())),
+                index: Box::new(ArmExpression::Literal(LiteralValue::Number(0.0))),
+            })
+        );
+    }
+
+    #[test]
+    fn reference_with_api_version() {
+        pretty_assertions::assert_eq!(
+            parse_expression("[reference('foo', '2021-01-01')]").expect("parseable"),
+            ArmExpression::Reference(ReferenceExpression {
+                resource_name: "foo".to_string(),
+                api_version: Some("2021-01-01".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn unclosed_call_reports_unbalanced_brackets() {
+        pretty_assertions::assert_eq!(
+            parse_expression("[concat('a', 'b'"),
+            Err(DisarmError::UnbalancedBrackets { offset: 16 })
+        );
+    }
+
+    #[test]
+    fn unknown_function_name_is_reported_with_its_offset() {
+        pretty_assertions::assert_eq!(
+            parse_expression("[notAFunction(1)]"),
+            Err(DisarmError::UnknownFunction {
+                offset: 1,
+                name: "notAFunction".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn leading_and_trailing_commas_are_empty_arguments() {
+        pretty_assertions::assert_eq!(
+            parse_expression("[concat(, 'a')]"),
+            Err(DisarmError::EmptyArgument { offset: 8 })
+        );
+        pretty_assertions::assert_eq!(
+            parse_expression("[concat('a',)]"),
+            Err(DisarmError::EmptyArgument { offset: 12 })
+        );
+    }
+
+    #[test]
+    fn wrong_argument_kind_surfaces_as_invalid_argument() {
+        pretty_assertions::assert_eq!(
+            parse_expression("[if('not a bool', 'a', 'b')]"),
+            Err(DisarmError::InvalidArgument {
+                offset: 1,
+                source: functions::FunctionError::WrongArgumentKind {
+                    name: "if".to_string(),
+                    index: 0,
+                    expected: functions::ArgKind::Bool,
+                    got: functions::ArgKind::String,
+                },
+            })
+        );
+    }
+
+    /// End-to-end: a malformed expression nested inside a real
+    /// `ArmTemplate` document deserializes to an `Err`, it doesn't panic.
+    #[test]
+    fn malformed_expression_in_a_template_deserializes_to_an_error() {
+        let json = r#"{
+            "parameters": null,
+            "variables": { "bad": "[concat('a', 'b'" },
+            "resources": [],
+            "outputs": null
+        }"#;
+
+        let result: Result<crate::ArmTemplate, _> = serde_json::from_str(json);
+        assert!(result.is_err(), "expected a deserialization error, got {result:?}");
+    }
+}